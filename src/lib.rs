@@ -1,3 +1,6 @@
+use std::marker::PhantomData;
+use std::ops::{Range, RangeInclusive};
+
 use rand::{RngCore, SeedableRng};
 use rand_seeder::Seeder;
 use rand_xoshiro::Xoshiro256StarStar;
@@ -12,6 +15,16 @@ use bevy::prelude::*;
 
 pub use rand::Rng as _;
 
+/// The bound required of a type used as the RNG backend for [`RngPlugin`],
+/// [`RootRng`] and [`Rng`].
+///
+/// This is implemented for every type that can plausibly serve as a backend
+/// (e.g. `Xoshiro256StarStar`, `ChaCha8Rng`, `SmallRng`), so you shouldn't
+/// need to implement it yourself.
+pub trait RngBackend: SeedableRng + RngCore + Clone + Send + Sync + 'static {}
+
+impl<R: SeedableRng + RngCore + Clone + Send + Sync + 'static> RngBackend for R {}
+
 /// `RngPlugin` allows you to inject a (optionally seeded) random number
 /// generator into your systems.
 ///
@@ -25,32 +38,96 @@ pub use rand::Rng as _;
 /// You are still responsible for deterministically generating random numbers
 /// _inside_ an individual system, which (currently) means you can't generate
 /// random numbers when iterating over entities, as entity iteration also isn't
-/// ordered currently.
-#[derive(Debug, Clone, Default, PartialEq, Eq, Hash, PartialOrd, Ord)]
-pub struct RngPlugin {
+/// ordered currently. For that case, use [`Rng::split_for`] (or
+/// [`rng_for_key`]) to derive an independent stream from a stable key, such
+/// as an entity's index, instead of relying on visitation order.
+///
+/// `RngPlugin` is generic over its backend `R`, which defaults to
+/// `Xoshiro256StarStar`. Use e.g. `RngPlugin::<rand_chacha::ChaCha8Rng>::from(seed)`
+/// to register a different algorithm, and `Local<bevy_rng::Rng<rand_chacha::ChaCha8Rng>>`
+/// to read from it; the two type parameters must match.
+pub struct RngPlugin<R = Xoshiro256StarStar> {
     seed: Option<Seed>,
+    _marker: PhantomData<R>,
+}
+
+// Manual impls below, instead of `derive`, because `R` only ever appears
+// behind `PhantomData` here: deriving would incorrectly require `R` itself to
+// implement these traits before `RngPlugin<R>` could.
+
+impl<R> Default for RngPlugin<R> {
+    fn default() -> Self {
+        Self {
+            seed: None,
+            _marker: PhantomData,
+        }
+    }
+}
+
+impl<R> std::fmt::Debug for RngPlugin<R> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("RngPlugin").field("seed", &self.seed).finish()
+    }
 }
 
-impl From<String> for RngPlugin {
+impl<R> Clone for RngPlugin<R> {
+    fn clone(&self) -> Self {
+        Self {
+            seed: self.seed.clone(),
+            _marker: PhantomData,
+        }
+    }
+}
+
+impl<R> PartialEq for RngPlugin<R> {
+    fn eq(&self, other: &Self) -> bool {
+        self.seed == other.seed
+    }
+}
+
+impl<R> Eq for RngPlugin<R> {}
+
+impl<R> std::hash::Hash for RngPlugin<R> {
+    fn hash<H: std::hash::Hasher>(&self, state: &mut H) {
+        self.seed.hash(state);
+    }
+}
+
+impl<R> PartialOrd for RngPlugin<R> {
+    fn partial_cmp(&self, other: &Self) -> Option<std::cmp::Ordering> {
+        self.seed.partial_cmp(&other.seed)
+    }
+}
+
+impl<R> Ord for RngPlugin<R> {
+    fn cmp(&self, other: &Self) -> std::cmp::Ordering {
+        self.seed.cmp(&other.seed)
+    }
+}
+
+impl<R> From<String> for RngPlugin<R> {
     fn from(seed: String) -> Self {
         Self {
             seed: Some(Seed::String(seed)),
+            _marker: PhantomData,
         }
     }
 }
 
-impl From<&str> for RngPlugin {
+impl<R> From<&str> for RngPlugin<R> {
     fn from(seed: &str) -> Self {
         Self {
             seed: Some(Seed::String(seed.to_owned())),
+            _marker: PhantomData,
         }
     }
 }
 
-impl From<u64> for RngPlugin {
+impl<R> From<u64> for RngPlugin<R> {
     fn from(seed: u64) -> Self {
         Self {
             seed: Some(Seed::Number(seed)),
+            _marker: PhantomData,
         }
     }
 }
@@ -61,21 +138,82 @@ enum Seed {
     String(String),
 }
 
-impl Plugin for RngPlugin {
+impl<R: RngBackend> Plugin for RngPlugin<R> {
     fn build(&self, app: &mut AppBuilder) {
         let rng = match &self.seed {
             Some(Seed::String(seed)) => Seeder::from(seed.as_str()).make_rng(),
-            Some(Seed::Number(num)) => Xoshiro256StarStar::seed_from_u64(*num),
-            None => Xoshiro256StarStar::from_entropy(),
+            Some(Seed::Number(num)) => R::seed_from_u64(*num),
+            None => R::from_entropy(),
         };
 
-        app.insert_resource(RootRng { rng });
+        app.insert_resource(RootRng::<R> { rng });
     }
 }
 
 #[derive(Debug, Clone, PartialEq, Eq)]
-struct RootRng {
-    rng: Xoshiro256StarStar,
+struct RootRng<R = Xoshiro256StarStar> {
+    rng: R,
+}
+
+/// Generates `next_<ty>_range`/`gen_range_<ty>`/`bounded_<ty>` for a given
+/// integer width, so the `u32` and `u64` flavors can't drift apart.
+///
+/// - `$next_range`/`$gen_range`/`$bounded`: the method names to define.
+/// - `$bounds_trait`: the `RangeBounds*` trait accepted by `$gen_range`.
+/// - `$draw`: the `RngCore` method used to draw a raw word (`next_u32`/`next_u64`).
+/// - `$base`/`$wide`: the sampled integer type and a type at least twice as
+///   wide, used for Lemire's multiply-reduce.
+macro_rules! uniform_int_range {
+    ($next_range:ident, $gen_range:ident, $bounded:ident, $bounds_trait:ident, $draw:ident, $base:ty, $wide:ty) => {
+        /// Returns a uniformly distributed integer in `[min, max)`.
+        ///
+        /// Uses Lemire's multiply-reduce with rejection, so (unlike scaling a
+        /// float) every value in range is equally likely.
+        ///
+        /// Panics if `min >= max`.
+        pub fn $next_range(&mut self, min: $base, max: $base) -> $base {
+            assert!(min < max, "cannot sample empty range");
+            min + self.$bounded(max - min)
+        }
+
+        /// Returns a uniformly distributed integer in the given range, which
+        /// may be either exclusive (`min..max`) or inclusive (`min..=max`).
+        ///
+        /// Panics if the range is empty.
+        pub fn $gen_range(&mut self, range: impl $bounds_trait) -> $base {
+            let (min, max) = range.bounds();
+            assert!(min <= max, "cannot sample empty range");
+
+            let width = max - min;
+            if width == <$base>::MAX {
+                // The range spans every representable value (only possible
+                // here when `min == 0` and `max == <$base>::MAX`), so every
+                // draw is already uniform over it: no rejection needed, and
+                // `width + 1` wouldn't fit in `$base` anyway.
+                return min.wrapping_add(self.inner.$draw());
+            }
+
+            min + self.$bounded(width + 1)
+        }
+
+        /// Draws a uniformly distributed integer in `[0, s)` using Lemire's
+        /// multiply-reduce with rejection, avoiding the bias that plain
+        /// float-scaling introduces for large `s`.
+        fn $bounded(&mut self, s: $base) -> $base {
+            loop {
+                let x = self.inner.$draw();
+                let m = (x as $wide) * (s as $wide);
+                let low = m as $base;
+                if low >= s {
+                    return (m >> <$base>::BITS) as $base;
+                }
+                let t = s.wrapping_neg() % s;
+                if low >= t {
+                    return (m >> <$base>::BITS) as $base;
+                }
+            }
+        }
+    };
 }
 
 /// The Rng resource.
@@ -83,12 +221,15 @@ struct RootRng {
 /// This wraps a random number generator.
 ///
 /// See the `rand::Rng` trait for more details on how to generate random data.
+///
+/// Generic over the backend `R`, which defaults to `Xoshiro256StarStar`; this
+/// must match the `R` used for the corresponding [`RngPlugin`].
 #[derive(Debug, Clone, PartialEq, Eq)]
-pub struct Rng {
-    inner: Xoshiro256StarStar,
+pub struct Rng<R = Xoshiro256StarStar> {
+    inner: R,
 }
 
-impl Rng {
+impl<R: RngBackend> Rng<R> {
     pub fn next_u32(&mut self) -> u32 {
         self.inner.next_u32()
     }
@@ -102,28 +243,337 @@ impl Rng {
         self.inner.gen::<f64>()
     }
 
-    pub fn next_u32_range(&mut self, min: u32, max: u32) -> u32 {
-        (self.next_f32() * (max - min) as f32) as u32 + min
-    }
-    pub fn next_u64_range(&mut self, min: u64, max: u64) -> u64 {
-        (self.next_f64() * (max - min) as f64) as u64 + min
-    }
     pub fn next_f32_range(&mut self, min: f32, max: f32) -> f32 {
         self.next_f32() * (max - min) + min
     }
     pub fn next_f64_range(&mut self, min: f64, max: f64) -> f64 {
         self.next_f64() * (max - min) + min
     }
+
+    uniform_int_range!(next_u32_range, gen_range_u32, bounded_u32, RangeBoundsU32, next_u32, u32, u64);
+    uniform_int_range!(next_u64_range, gen_range_u64, bounded_u64, RangeBoundsU64, next_u64, u64, u128);
+
+    /// Derives an independent, deterministic child stream keyed by `key`.
+    ///
+    /// Draws a value from a clone of this stream and mixes it with `key`,
+    /// then reseeds a fresh backend from the result, so that distinct keys
+    /// (e.g. an entity's index) reliably land on distinct, reproducible
+    /// streams regardless of the order they're requested in.
+    ///
+    /// This takes `&self`, so it never advances `self`'s own stream: calling
+    /// `split_for(key)` twice with the same key against an otherwise-untouched
+    /// `Rng` returns bit-identical output both times. That's the point for
+    /// "derive this entity's generator once on spawn", but it's a footgun if
+    /// you call it again with the same key expecting fresh randomness each
+    /// frame — advance `self` first (e.g. draw from it) or mix something that
+    /// changes, like the frame count, into `key`.
+    pub fn split_for(&self, key: u64) -> Self {
+        let mut source = self.inner.clone();
+        let x = source.next_u64();
+        // splitmix64's golden-ratio increment, used here purely to mix `key`
+        // and the drawn value rather than as a generator in its own right.
+        let seed = x ^ key.wrapping_mul(0x9E3779B97F4A7C15);
+        Self {
+            inner: R::seed_from_u64(seed),
+        }
+    }
+
+    /// Draws a sample from the normal distribution with the given `mean` and
+    /// `std_dev`, using the Box–Muller transform.
+    pub fn next_normal(&mut self, mean: f64, std_dev: f64) -> f64 {
+        // `next_f64` draws from [0, 1), but Box-Muller needs u1 in (0, 1] to
+        // avoid taking ln(0), so nudge it into range.
+        let u1 = 1.0 - self.next_f64();
+        let u2 = self.next_f64();
+
+        let z0 = (-2.0 * u1.ln()).sqrt() * (std::f64::consts::TAU * u2).cos();
+        mean + std_dev * z0
+    }
+
+    /// Draws a sample from the exponential distribution with rate `lambda`,
+    /// via inverse-CDF sampling.
+    ///
+    /// Panics if `lambda <= 0.0`.
+    pub fn next_exponential(&mut self, lambda: f64) -> f64 {
+        assert!(lambda > 0.0, "lambda must be positive");
+        // `next_f64` draws from [0, 1), so `1 - u` stays in (0, 1], avoiding
+        // taking ln(0).
+        let u = self.next_f64();
+        -(1.0 - u).ln() / lambda
+    }
+
+    /// Draws a sample from the Poisson distribution with the given `mean`,
+    /// using Knuth's multiplication method.
+    ///
+    /// This runs in O(mean) time, so for large means (roughly `mean > 30`),
+    /// switch to a rejection-based method instead.
+    ///
+    /// Panics if `mean < 0.0`.
+    pub fn next_poisson(&mut self, mean: f64) -> u64 {
+        assert!(mean >= 0.0, "mean must be non-negative");
+        let l = (-mean).exp();
+        let mut k = 0;
+        let mut p = 1.0;
+
+        loop {
+            k += 1;
+            p *= self.next_f64();
+            if p <= l {
+                break;
+            }
+        }
+
+        k - 1
+    }
 }
 
-impl FromWorld for Rng {
+/// Defines a `RangeBounds*` trait (accepted by `gen_range_*`) plus its
+/// `Range<$ty>`/`RangeInclusive<$ty>` impls, so the `u32` and `u64` flavors
+/// can't drift apart.
+///
+/// Bounds are reported inclusive (`(min, max)`, both ends sampleable), not
+/// the exclusive `(min, max)` `next_*_range` takes: an exclusive upper bound
+/// can't represent `..=<$ty>::MAX` without overflowing `$ty`, and that's
+/// exactly the "any value at all" range a caller is most likely to pass.
+macro_rules! range_bounds {
+    ($trait:ident, $ty:ty) => {
+        #[doc = concat!(
+            "Bounds accepted by [`Rng::gen_range_", stringify!($ty), "`]: either `Range<",
+            stringify!($ty), ">` (exclusive) or `RangeInclusive<", stringify!($ty), ">` (inclusive)."
+        )]
+        pub trait $trait {
+            /// Returns the equivalent inclusive `(min, max)` bounds.
+            fn bounds(self) -> ($ty, $ty);
+        }
+
+        impl $trait for Range<$ty> {
+            fn bounds(self) -> ($ty, $ty) {
+                assert!(self.start < self.end, "cannot sample empty range");
+                (self.start, self.end - 1)
+            }
+        }
+
+        impl $trait for RangeInclusive<$ty> {
+            fn bounds(self) -> ($ty, $ty) {
+                (*self.start(), *self.end())
+            }
+        }
+    };
+}
+
+range_bounds!(RangeBoundsU32, u32);
+range_bounds!(RangeBoundsU64, u64);
+
+impl<R: RngBackend> FromWorld for Rng<R> {
     fn from_world(world: &mut World) -> Self {
-        let inner = match world.get_resource::<RootRng>() {
-            Some(rng) => Xoshiro256StarStar::from_rng(rng.rng.clone())
-                .expect("failed to create rng"),
-            None => Xoshiro256StarStar::from_entropy(),
+        let inner = match world.get_resource::<RootRng<R>>() {
+            Some(rng) => R::from_rng(rng.rng.clone()).expect("failed to create rng"),
+            None => R::from_entropy(),
         };
 
         Self { inner }
     }
 }
+
+/// Derives an [`Rng`] keyed by `key` from the app's root rng, without going
+/// through `Local<Rng>`.
+///
+/// This is [`Rng::split_for`] for callers that only have access to the
+/// `World`, e.g. when deriving a stream per entity from its index.
+///
+/// Like [`Rng::split_for`], this never advances the root stream, so calling
+/// it again with the same key returns bit-identical output; don't call it
+/// repeatedly with the same key expecting new randomness each time.
+///
+/// Panics if an `RngPlugin<R>` hasn't been added to the app.
+pub fn rng_for_key<R: RngBackend>(world: &World, key: u64) -> Rng<R> {
+    let root = world
+        .get_resource::<RootRng<R>>()
+        .expect("RngPlugin must be added before calling rng_for_key");
+
+    Rng {
+        inner: root.rng.clone(),
+    }
+    .split_for(key)
+}
+
+#[cfg(test)]
+mod range_tests {
+    use super::*;
+
+    fn rng(seed: u64) -> Rng<Xoshiro256StarStar> {
+        Rng {
+            inner: Xoshiro256StarStar::seed_from_u64(seed),
+        }
+    }
+
+    #[test]
+    #[should_panic(expected = "cannot sample empty range")]
+    fn next_u32_range_panics_on_equal_bounds() {
+        rng(0).next_u32_range(5, 5);
+    }
+
+    #[test]
+    #[should_panic(expected = "cannot sample empty range")]
+    fn next_u64_range_panics_on_inverted_bounds() {
+        rng(0).next_u64_range(5, 1);
+    }
+
+    #[test]
+    fn gen_range_u32_inclusive_full_domain_does_not_panic() {
+        let mut rng = rng(0);
+        for _ in 0..8 {
+            rng.gen_range_u32(0..=u32::MAX);
+        }
+    }
+
+    #[test]
+    fn gen_range_u64_inclusive_full_domain_does_not_panic() {
+        let mut rng = rng(0);
+        for _ in 0..8 {
+            rng.gen_range_u64(0..=u64::MAX);
+        }
+    }
+
+    #[test]
+    fn gen_range_u32_inclusive_near_max_is_in_bounds() {
+        let mut rng = rng(5);
+        for _ in 0..32 {
+            let v = rng.gen_range_u32(u32::MAX - 2..=u32::MAX);
+            assert!(v >= u32::MAX - 2, "{v} is out of range");
+        }
+    }
+
+    #[test]
+    #[should_panic(expected = "cannot sample empty range")]
+    fn gen_range_u32_inclusive_panics_on_inverted_bounds() {
+        rng(0).gen_range_u32(5..=3);
+    }
+
+    #[test]
+    #[should_panic(expected = "cannot sample empty range")]
+    fn gen_range_u64_panics_on_equal_exclusive_bounds() {
+        rng(0).gen_range_u64(5..5);
+    }
+
+    #[test]
+    fn next_u32_range_of_width_one_always_returns_min() {
+        let mut rng = rng(1);
+        for _ in 0..32 {
+            assert_eq!(rng.next_u32_range(7, 8), 7);
+        }
+    }
+
+    #[test]
+    fn next_u64_range_of_width_one_always_returns_min() {
+        let mut rng = rng(1);
+        for _ in 0..32 {
+            assert_eq!(rng.next_u64_range(7, 8), 7);
+        }
+    }
+
+    #[test]
+    fn gen_range_u32_small_range_covers_every_value_and_is_in_bounds() {
+        let mut rng = rng(42);
+        let mut seen = [0u32; 3];
+        for _ in 0..300 {
+            let v = rng.gen_range_u32(0..3);
+            assert!(v < 3, "{v} is out of range 0..3");
+            seen[v as usize] += 1;
+        }
+        assert!(
+            seen.iter().all(|&count| count > 0),
+            "expected every value in 0..3 to come up at least once, got {seen:?}"
+        );
+    }
+
+    #[test]
+    fn gen_range_u32_inclusive_small_range_covers_every_value_and_is_in_bounds() {
+        let mut rng = rng(42);
+        let mut seen = [0u32; 3];
+        for _ in 0..300 {
+            let v = rng.gen_range_u32(0..=2);
+            assert!(v <= 2, "{v} is out of range 0..=2");
+            seen[v as usize] += 1;
+        }
+        assert!(
+            seen.iter().all(|&count| count > 0),
+            "expected every value in 0..=2 to come up at least once, got {seen:?}"
+        );
+    }
+}
+
+#[cfg(test)]
+mod split_for_tests {
+    use super::*;
+
+    fn root() -> Rng<Xoshiro256StarStar> {
+        Rng {
+            inner: Xoshiro256StarStar::seed_from_u64(7),
+        }
+    }
+
+    #[test]
+    fn split_for_is_reproducible_for_the_same_key() {
+        let root = root();
+        let mut a = root.split_for(42);
+        let mut b = root.split_for(42);
+        assert_eq!(a.next_u64(), b.next_u64());
+    }
+
+    #[test]
+    fn split_for_diverges_across_keys() {
+        let root = root();
+        let mut outputs: Vec<u64> = (0..8).map(|key| root.split_for(key).next_u64()).collect();
+        outputs.sort_unstable();
+        outputs.dedup();
+        assert_eq!(outputs.len(), 8, "expected 8 distinct streams, got {outputs:?}");
+    }
+}
+
+#[cfg(test)]
+mod distribution_tests {
+    use super::*;
+
+    fn rng(seed: u64) -> Rng<Xoshiro256StarStar> {
+        Rng {
+            inner: Xoshiro256StarStar::seed_from_u64(seed),
+        }
+    }
+
+    #[test]
+    fn next_poisson_of_zero_mean_is_always_zero() {
+        let mut rng = rng(3);
+        for _ in 0..32 {
+            assert_eq!(rng.next_poisson(0.0), 0);
+        }
+    }
+
+    #[test]
+    #[should_panic(expected = "mean must be non-negative")]
+    fn next_poisson_panics_on_negative_mean() {
+        rng(0).next_poisson(-5.0);
+    }
+
+    #[test]
+    #[should_panic(expected = "lambda must be positive")]
+    fn next_exponential_panics_on_zero_lambda() {
+        rng(0).next_exponential(0.0);
+    }
+
+    #[test]
+    #[should_panic(expected = "lambda must be positive")]
+    fn next_exponential_panics_on_negative_lambda() {
+        rng(0).next_exponential(-1.0);
+    }
+
+    #[test]
+    fn next_normal_is_reproducible_for_a_fixed_seed() {
+        assert_eq!(rng(11).next_normal(0.0, 1.0), rng(11).next_normal(0.0, 1.0));
+    }
+
+    #[test]
+    fn next_exponential_is_reproducible_for_a_fixed_seed() {
+        assert_eq!(rng(11).next_exponential(1.0), rng(11).next_exponential(1.0));
+    }
+}